@@ -3,6 +3,7 @@
 
 mod generated;
 
+use chrono::{DateTime, Datelike, NaiveDateTime};
 use fluent::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
 use num_format::Locale;
 use serde::Serialize;
@@ -30,36 +31,378 @@ macro_rules! tr_strs {
     };
 }
 
-fn remapped_lang_name(lang: &LanguageIdentifier) -> &str {
-    let region = match &lang.region {
-        Some(region) => Some(region.as_str()),
-        None => None,
+/// Pseudolocalization transforms, used to help maintainers spot
+/// untranslated or truncation-prone strings without needing real
+/// translations. Mirrors the accented/bidi transforms used by
+/// Firefox's Fluent DOM overlay.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PseudoTranslation {
+    /// Map ASCII letters to accented look-alikes, and pad the string out
+    /// by ~30-50% to help spot strings that will be truncated once
+    /// translated.
+    Accented,
+    /// Wrap the text in Unicode bidi override characters to simulate
+    /// right-to-left rendering.
+    Bidi,
+}
+
+fn accented_char(c: char) -> char {
+    let mapped = match c.to_ascii_lowercase() {
+        'a' => 'à',
+        'b' => 'ḅ',
+        'c' => 'ç',
+        'd' => 'ḓ',
+        'e' => 'ë',
+        'f' => 'ḟ',
+        'g' => 'ĝ',
+        'h' => 'ĥ',
+        'i' => 'î',
+        'j' => 'ĵ',
+        'k' => 'ķ',
+        'l' => 'ĺ',
+        'm' => 'ḿ',
+        'n' => 'ñ',
+        'o' => 'ô',
+        'p' => 'ṕ',
+        'r' => 'ř',
+        's' => 'š',
+        't' => 'ť',
+        'u' => 'ü',
+        'v' => 'ṽ',
+        'w' => 'ŵ',
+        'y' => 'ý',
+        'z' => 'ž',
+        _ => return c,
     };
-    match lang.language.as_str() {
-        "en" => {
-            match region {
-                Some("GB") | Some("AU") => "en-GB",
-                // go directly to fallback
-                _ => "templates",
+    if c.is_ascii_uppercase() {
+        mapped.to_uppercase().next().unwrap_or(mapped)
+    } else {
+        mapped
+    }
+}
+
+/// Pad a pseudolocalized string so it is roughly 30-50% longer than the
+/// original, to help surface layouts that will truncate or wrap badly
+/// once real translations (which are often longer than English) land.
+fn pad_for_translation_growth(text: &str) -> String {
+    let extra = ((text.chars().count() as f32) * 0.4).ceil() as usize;
+    if extra == 0 {
+        text.to_string()
+    } else {
+        format!("{} {}", text, "·".repeat(extra))
+    }
+}
+
+/// Argument values Fluent has already substituted into the formatted
+/// string, so the pseudolocalization transform can avoid mangling them.
+/// Numbers are rendered through the same `NumberFormat` the bundle uses,
+/// so the needle matches the grouped/locale-formatted text that actually
+/// appears rather than Rust's default `Display` output.
+fn rendered_arg_values(lang: &LanguageIdentifier, args: Option<&FluentArgs>) -> Vec<String> {
+    let mut rendered = vec![];
+    if let Some(args) = args {
+        let numbers = NumberFormat::for_lang(lang);
+        for (_, value) in args.iter() {
+            match value {
+                FluentValue::String(s) => rendered.push(s.to_string()),
+                FluentValue::Number(n) => rendered.push(numbers.format(n)),
+                _ => {}
             }
         }
-        "zh" => match region {
-            Some("TW") | Some("HK") => "zh-TW",
-            _ => "zh-CN",
-        },
-        "pt" => {
-            if let Some("PT") = region {
-                "pt-PT"
-            } else {
-                "pt-BR"
+    }
+    rendered
+}
+
+/// Apply `transform` to the parts of `text` that did not come from a
+/// substituted argument, leaving placeable-derived text untouched. This
+/// is the equivalent of the placeable-skipping behaviour of Firefox's
+/// Fluent DOM overlay, applied after the string has already been
+/// resolved rather than on the pattern's AST.
+fn transform_skipping_args(
+    text: &str,
+    lang: &LanguageIdentifier,
+    args: Option<&FluentArgs>,
+    transform: impl Fn(&str) -> String,
+) -> String {
+    let protected = rendered_arg_values(lang, args);
+    if protected.is_empty() {
+        return transform(text);
+    }
+
+    let mut out = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        // Consider every protected needle at once and take whichever
+        // starts earliest, breaking ties in favour of the longest match,
+        // so a short value like "2" can't steal a match that a longer,
+        // earlier-or-equal needle should own.
+        let next = protected
+            .iter()
+            .filter(|n| !n.is_empty())
+            .filter_map(|n| rest.find(n.as_str()).map(|pos| (pos, n)))
+            .min_by_key(|(pos, n)| (*pos, std::cmp::Reverse(n.len())));
+
+        match next {
+            Some((pos, needle)) => {
+                out += &transform(&rest[..pos]);
+                out += needle;
+                rest = &rest[pos + needle.len()..];
+            }
+            None => {
+                out += &transform(rest);
+                break;
+            }
+        }
+    }
+    out
+}
+
+fn pseudolocalize(
+    text: &str,
+    lang: &LanguageIdentifier,
+    args: Option<&FluentArgs>,
+    kind: PseudoTranslation,
+) -> String {
+    match kind {
+        PseudoTranslation::Accented => {
+            let accented = transform_skipping_args(text, lang, args, |s| {
+                s.chars().map(accented_char).collect()
+            });
+            pad_for_translation_growth(&accented)
+        }
+        PseudoTranslation::Bidi => {
+            transform_skipping_args(text, lang, args, |s| {
+                format!("\u{202e}{}\u{202c}", s)
+            })
+        }
+    }
+}
+
+/// The set of languages actually bundled in the binary, derived from the
+/// generated `STRINGS` table. Entries that aren't valid BCP 47 tags (such
+/// as the `templates` fallback key) are silently skipped.
+fn available_languages() -> Vec<LanguageIdentifier> {
+    STRINGS.keys().filter_map(|code| code.parse().ok()).collect()
+}
+
+/// Negotiate between the user's ordered language preferences and the
+/// languages actually bundled in the binary, returning supported locales
+/// in priority order. For each preference, tries (in order) an exact
+/// match, a language+region match, a language-only match, and finally a
+/// macro-language fallback, analogous to `fluent-langneg`'s matching
+/// behaviour. English preferences are the exception: they only match a
+/// bundled English regional variant, and no later preference is tried
+/// once an English one is reached.
+pub fn negotiate_languages(
+    requested: &[LanguageIdentifier],
+    available: &[LanguageIdentifier],
+) -> Vec<LanguageIdentifier> {
+    let mut out = vec![];
+    for pref in requested {
+        if pref.language == "en" {
+            // English's bundled regional variants (en-GB/en-AU) are still
+            // matched, but bare `en` (and any other region) relies on the
+            // always-appended, 100%-coverage English template instead.
+            // Either way, any further, lower-priority preferences are
+            // skipped once English is reached.
+            if let Some(region) = pref.region {
+                let mut regions = vec![region.as_str()];
+                // en-AU has no bundle of its own, and shares en-GB's, as in
+                // resolve_lang_name()
+                if region.as_str() == "AU" {
+                    regions.push("GB");
+                }
+                if let Some(matched) = regions.iter().find_map(|wanted| {
+                    available.iter().find(|a| {
+                        a.language == pref.language
+                            && a.region.as_ref().map(|r| r.as_str()) == Some(*wanted)
+                    })
+                }) {
+                    if !out.contains(matched) {
+                        out.push(matched.clone());
+                    }
+                }
+            }
+            break;
+        }
+        if let Some(matched) = negotiate_one(pref, available) {
+            if !out.contains(&matched) {
+                out.push(matched);
             }
         }
-        "ga" => "ga-IE",
-        "hy" => "hy-AM",
-        "nb" => "nb-NO",
-        "sv" => "sv-SE",
-        other => other,
     }
+    out
+}
+
+fn negotiate_one(
+    pref: &LanguageIdentifier,
+    available: &[LanguageIdentifier],
+) -> Option<LanguageIdentifier> {
+    // exact match
+    if let Some(exact) = available.iter().find(|a| *a == pref) {
+        return Some(exact.clone());
+    }
+    // language+region match (ignoring script)
+    if let Some(region) = pref.region {
+        if let Some(found) = available
+            .iter()
+            .find(|a| a.language == pref.language && a.region == Some(region))
+        {
+            return Some(found.clone());
+        }
+    }
+    // language-only match: maximize the preference via likely subtags and
+    // score every same-language candidate against it, so e.g. bare `zh`
+    // deterministically prefers zh-Hans-CN and `zh-HK` prefers zh-Hant-HK,
+    // instead of whichever same-language bundle `available` happens to
+    // list first (its order isn't guaranteed, since it's derived from a
+    // map). Ties are broken by tag so the result never depends on
+    // iteration order.
+    let maximized_pref = add_likely_subtags(&canonicalize(pref));
+    if let Some(found) = available
+        .iter()
+        .filter(|a| a.language == pref.language)
+        .max_by_key(|a| {
+            let score = subtag_match_score(&maximized_pref, &add_likely_subtags(a)).unwrap_or(0);
+            (score, std::cmp::Reverse(a.to_string()))
+        })
+    {
+        return Some(found.clone());
+    }
+    // macro-language fallback, e.g. Cantonese falling back to Chinese
+    if let Some(macro_lang) = macro_language(pref.language.as_str()) {
+        if let Some(found) = available.iter().find(|a| a.language.as_str() == macro_lang) {
+            return Some(found.clone());
+        }
+    }
+    None
+}
+
+/// A short list of macro-language fallbacks, for specific language
+/// subtags that should fall back to a broader language if we don't have
+/// a bundle for the specific one.
+fn macro_language(language: &str) -> Option<&'static str> {
+    match language {
+        "yue" => Some("zh"),
+        "nn" | "nb" => Some("no"),
+        _ => None,
+    }
+}
+
+/// Map a handful of deprecated/legacy language subtags to their modern
+/// equivalents, and rely on [unic_langid]'s own parsing to normalize case.
+fn canonicalize(lang: &LanguageIdentifier) -> LanguageIdentifier {
+    let mut lang = lang.clone();
+    let modern = match lang.language.as_str() {
+        "iw" => Some("he"),
+        "in" => Some("id"),
+        "ji" => Some("yi"),
+        "mo" => Some("ro"),
+        _ => None,
+    };
+    if let Some(modern) = modern {
+        lang.language = modern.parse().unwrap();
+    }
+    lang
+}
+
+/// A small likely-subtags table, covering the language/script/region
+/// ambiguities `remapped_lang_name` used to hand-maintain (en-GB/AU,
+/// zh-TW vs zh-CN, pt-PT vs pt-BR, ga-IE, hy-AM, nb-NO, sv-SE). Mirrors
+/// the shape of CLDR's likely-subtags data, maximizing a partial tag to
+/// the script/region it most likely implies.
+const LIKELY_SUBTAGS: &[(&str, &str)] = &[
+    ("zh", "zh-Hans-CN"),
+    ("zh-TW", "zh-Hant-TW"),
+    ("zh-HK", "zh-Hant-HK"),
+    ("zh-MO", "zh-Hant-MO"),
+    ("zh-Hant", "zh-Hant-TW"),
+    ("zh-Hans", "zh-Hans-CN"),
+    ("pt", "pt-Latn-BR"),
+    ("pt-PT", "pt-Latn-PT"),
+    ("ga", "ga-Latn-IE"),
+    ("hy", "hy-Armn-AM"),
+    ("nb", "nb-Latn-NO"),
+    ("sv", "sv-Latn-SE"),
+    ("en", "en-Latn-US"),
+    ("en-GB", "en-Latn-GB"),
+    ("en-AU", "en-Latn-AU"),
+];
+
+/// Fill in the script/region implied by a language (and optionally
+/// region), e.g. `zh` -> `zh-Hans-CN`, `zh-TW` -> `zh-Hant-TW`. Already
+/// fully-specified tags are returned unchanged.
+///
+/// Of the table entries whose language (and region/script, if the entry
+/// specifies one) match `lang`, the most specific one wins, rather than
+/// whichever happens to be declared first: otherwise a generic entry
+/// like `zh` (which matches any region) would shadow a more specific one
+/// like `zh-TW` purely because of table order.
+fn add_likely_subtags(lang: &LanguageIdentifier) -> LanguageIdentifier {
+    if lang.script.is_some() && lang.region.is_some() {
+        return lang.clone();
+    }
+    LIKELY_SUBTAGS
+        .iter()
+        .filter_map(|(from, to)| {
+            let from: LanguageIdentifier = from.parse().unwrap();
+            let matches = from.language == lang.language
+                && (from.region.is_none() || from.region == lang.region)
+                && (from.script.is_none() || from.script == lang.script);
+            if !matches {
+                return None;
+            }
+            let specificity = from.region.is_some() as u8 + from.script.is_some() as u8;
+            Some((specificity, to))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, to)| to.parse().unwrap())
+        .unwrap_or_else(|| lang.clone())
+}
+
+/// Score how well two maximized language identifiers match: the language
+/// subtag must match for any score to be returned, with extra points for
+/// a matching script and a matching region.
+fn subtag_match_score(a: &LanguageIdentifier, b: &LanguageIdentifier) -> Option<u8> {
+    if a.language != b.language {
+        return None;
+    }
+    let mut score = 1;
+    if a.script == b.script {
+        score += 1;
+    }
+    if a.region == b.region {
+        score += 1;
+    }
+    Some(score)
+}
+
+/// Resolve `lang` to the best-matching key in the generated `STRINGS`
+/// table, by canonicalizing `lang`, expanding likely subtags on both sides
+/// and maximizing the number of matched subtags, rather than relying on
+/// a hand-maintained remapping table for each new region/translation.
+fn resolve_lang_name(lang: &LanguageIdentifier) -> Option<&'static str> {
+    if lang.language == "en" {
+        // the English template text is a special case: it is not one of
+        // the bundled translations, but the 100%-coverage source text,
+        // and must stay keyed separately from the negotiable languages.
+        return match lang.region.as_ref().map(|r| r.as_str()) {
+            Some("GB") | Some("AU") if STRINGS.contains_key("en-GB") => Some("en-GB"),
+            _ => STRINGS.contains_key("templates").then(|| "templates"),
+        };
+    }
+
+    let maximized = add_likely_subtags(&canonicalize(lang));
+    STRINGS
+        .keys()
+        .filter(|key| **key != "templates")
+        .filter_map(|key| {
+            let candidate: LanguageIdentifier = key.parse().ok()?;
+            let score = subtag_match_score(&maximized, &add_likely_subtags(&candidate))?;
+            Some((score, *key))
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, key)| key)
 }
 
 /// Some sample text for testing purposes.
@@ -93,14 +436,27 @@ one-arg-key = fake Polish {$one}
 /// extra_text may contain resources loaded from the filesystem
 /// at runtime. If it contains errors, they will not prevent a
 /// bundle from being returned.
+/// Parse/duplicate-key failures are recorded onto `diagnostics` instead
+/// of being printed, so callers (and the audit/"assert no fallbacks" use
+/// case) can see them programmatically.
 fn get_bundle(
     text: &str,
     extra_text: String,
     locales: &[LanguageIdentifier],
+    diagnostics: &mut Vec<TranslationDiagnostic>,
 ) -> Option<FluentBundle<FluentResource>> {
+    let lang = locales.first().map(ToString::to_string).unwrap_or_default();
+
     let res = FluentResource::try_new(text.into())
         .map_err(|e| {
-            println!("Unable to parse translations file: {:?}", e);
+            diagnostics.push(TranslationDiagnostic {
+                key: String::new(),
+                lang: lang.clone(),
+                kind: DiagnosticKind::BundleError(format!(
+                    "unable to parse translations file: {:?}",
+                    e
+                )),
+            });
         })
         .ok()?;
 
@@ -108,19 +464,35 @@ fn get_bundle(
     bundle
         .add_resource(res)
         .map_err(|e| {
-            println!("Duplicate key detected in translation file: {:?}", e);
+            diagnostics.push(TranslationDiagnostic {
+                key: String::new(),
+                lang: lang.clone(),
+                kind: DiagnosticKind::BundleError(format!(
+                    "duplicate key detected in translation file: {:?}",
+                    e
+                )),
+            });
         })
         .ok()?;
 
     if !extra_text.is_empty() {
         match FluentResource::try_new(extra_text) {
             Ok(res) => bundle.add_resource_overriding(res),
-            Err((_res, e)) => println!("Unable to parse translations file: {:?}", e),
+            Err((_res, e)) => diagnostics.push(TranslationDiagnostic {
+                key: String::new(),
+                lang: lang.clone(),
+                kind: DiagnosticKind::BundleError(format!(
+                    "unable to parse translations file: {:?}",
+                    e
+                )),
+            }),
         }
     }
 
     // add numeric formatter
     set_bundle_formatter_for_langs(&mut bundle, locales);
+    // add the DATETIME() builtin
+    add_datetime_function(&mut bundle, locales);
 
     Some(bundle)
 }
@@ -129,6 +501,7 @@ fn get_bundle(
 fn get_bundle_with_extra(
     text: &str,
     lang: Option<LanguageIdentifier>,
+    diagnostics: &mut Vec<TranslationDiagnostic>,
 ) -> Option<FluentBundle<FluentResource>> {
     let mut extra_text = "".into();
     if cfg!(test) {
@@ -154,7 +527,7 @@ fn get_bundle_with_extra(
     };
     locales.push("en-US".parse().unwrap());
 
-    get_bundle(text, extra_text, &locales)
+    get_bundle(text, extra_text, &locales, diagnostics)
 }
 
 #[derive(Clone)]
@@ -181,25 +554,30 @@ impl I18n {
     }
 
     pub fn new<S: AsRef<str>>(locale_codes: &[S]) -> Self {
-        let mut input_langs = vec![];
+        Self::new_inner(locale_codes, None)
+    }
+
+    /// Like [I18n::new()], but wraps every resolved string through a
+    /// pseudolocalization transform, to help maintainers spot untranslated
+    /// or truncation-prone UI strings without needing real translations.
+    pub fn new_pseudo<S: AsRef<str>>(locale_codes: &[S], kind: PseudoTranslation) -> Self {
+        Self::new_inner(locale_codes, Some(kind))
+    }
+
+    fn new_inner<S: AsRef<str>>(locale_codes: &[S], pseudo: Option<PseudoTranslation>) -> Self {
+        let input_langs: Vec<LanguageIdentifier> = locale_codes
+            .iter()
+            .filter_map(|code| code.as_ref().parse().ok())
+            .collect();
+
+        let negotiated = negotiate_languages(&input_langs, &available_languages());
+
         let mut bundles = Vec::with_capacity(locale_codes.len() + 1);
         let mut resource_text = vec![];
-
-        for code in locale_codes {
-            let code = code.as_ref();
-            if let Ok(lang) = code.parse::<LanguageIdentifier>() {
-                input_langs.push(lang.clone());
-                if lang.language == "en" {
-                    // if English was listed, any further preferences are skipped,
-                    // as the template has 100% coverage, and we need to ensure
-                    // it is tried prior to any other langs.
-                    break;
-                }
-            }
-        }
+        let mut diagnostics = vec![];
 
         let mut output_langs = vec![];
-        for lang in input_langs {
+        for lang in negotiated {
             // if the language is bundled in the binary
             if let Some(text) = ftl_localized_text(&lang).or_else(|| {
                 // when testing, allow missing translations
@@ -209,12 +587,14 @@ impl I18n {
                     None
                 }
             }) {
-                if let Some(bundle) = get_bundle_with_extra(&text, Some(lang.clone())) {
+                // a failure here is recorded as a BundleError diagnostic by
+                // get_bundle_with_extra, so the language is simply skipped
+                if let Some(bundle) =
+                    get_bundle_with_extra(&text, Some(lang.clone()), &mut diagnostics)
+                {
                     resource_text.push(text);
                     bundles.push(bundle);
                     output_langs.push(lang);
-                } else {
-                    println!("Failed to create bundle for {:?}", lang.language)
                 }
             }
         }
@@ -222,7 +602,8 @@ impl I18n {
         // add English templates
         let template_lang = "en-US".parse().unwrap();
         let template_text = ftl_localized_text(&template_lang).unwrap();
-        let template_bundle = get_bundle_with_extra(&template_text, None).unwrap();
+        let template_bundle =
+            get_bundle_with_extra(&template_text, None, &mut diagnostics).unwrap();
         resource_text.push(template_text);
         bundles.push(template_bundle);
         output_langs.push(template_lang);
@@ -239,6 +620,8 @@ impl I18n {
                 bundles,
                 langs: output_langs,
                 resource_text,
+                pseudo,
+                diagnostics,
             })),
         }
     }
@@ -261,7 +644,16 @@ impl I18n {
     }
 
     fn tr_<'a>(&'a self, key: &str, args: Option<FluentArgs>) -> Cow<'a, str> {
-        for bundle in &self.inner.lock().unwrap().bundles {
+        let mut inner = self.inner.lock().unwrap();
+        let last_index = inner.bundles.len().saturating_sub(1);
+
+        // collect diagnostics and the resolved string locally, so we don't
+        // need to mutate `inner` while a bundle's `format_pattern()` output
+        // (borrowed from `inner.bundles`) is still in scope
+        let mut pending_diagnostics = vec![];
+        let mut resolved = None;
+
+        for (index, bundle) in inner.bundles.iter().enumerate() {
             let msg = match bundle.get_message(key) {
                 Some(msg) => msg,
                 // not translated in this bundle
@@ -276,15 +668,54 @@ impl I18n {
 
             let mut errs = vec![];
             let out = bundle.format_pattern(pat, args.as_ref(), &mut errs);
+            let lang = inner.langs[index].to_string();
             if !errs.is_empty() {
-                println!("Error(s) in translation '{}': {:?}", key, errs);
+                pending_diagnostics.push(TranslationDiagnostic {
+                    key: key.to_string(),
+                    lang: lang.clone(),
+                    kind: DiagnosticKind::FormatError(format!("{:?}", errs)),
+                });
             }
+            if index == last_index && last_index > 0 {
+                pending_diagnostics.push(TranslationDiagnostic {
+                    key: key.to_string(),
+                    lang,
+                    kind: DiagnosticKind::FellBackToTemplate,
+                });
+            }
+
             // clone so we can discard args
-            return out.to_string().into();
+            resolved = Some(if let Some(kind) = inner.pseudo {
+                pseudolocalize(&out, &inner.langs[index], args.as_ref(), kind)
+            } else {
+                out.to_string()
+            });
+            break;
+        }
+
+        if resolved.is_none() {
+            pending_diagnostics.push(TranslationDiagnostic {
+                key: key.to_string(),
+                lang: inner.langs.first().map(ToString::to_string).unwrap_or_default(),
+                kind: DiagnosticKind::MissingKey,
+            });
         }
+        inner.diagnostics.extend(pending_diagnostics);
 
         // return the key name if it was missing
-        key.to_string().into()
+        match resolved {
+            Some(s) => s.into(),
+            None => key.to_string().into(),
+        }
+    }
+
+    /// Drain and return any diagnostics (missing keys, format errors,
+    /// fallbacks to the English template, or bundle errors recorded while
+    /// constructing this I18n) recorded since the last call.
+    /// Allows a "translation coverage" report, or a test mode that
+    /// asserts no fallbacks occurred, without parsing stdout output.
+    pub fn take_diagnostics(&self) -> Vec<TranslationDiagnostic> {
+        std::mem::take(&mut self.inner.lock().unwrap().diagnostics)
     }
 
     /// Return text from configured locales for use with the JS Fluent implementation.
@@ -293,23 +724,27 @@ impl I18n {
         ResourcesForJavascript {
             langs: inner.langs.iter().map(ToString::to_string).collect(),
             resources: inner.resource_text.clone(),
+            direction: text_direction_for_langs(&inner.langs),
         }
     }
+
+    /// The text direction of the primary negotiated language, for setting
+    /// UI layout direction.
+    pub fn text_direction(&self) -> CharacterDirection {
+        text_direction_for_langs(&self.inner.lock().unwrap().langs)
+    }
 }
 
 /// This temporarily behaves like the older code; in the future we could either
 /// access each &str separately, or load them on demand.
 fn ftl_localized_text(lang: &LanguageIdentifier) -> Option<String> {
-    let lang = remapped_lang_name(lang);
-    if let Some(module) = STRINGS.get(lang) {
-        let mut text = String::new();
-        for module_text in module.values() {
-            text.push_str(module_text)
-        }
-        Some(text)
-    } else {
-        None
+    let lang = resolve_lang_name(lang)?;
+    let module = STRINGS.get(lang)?;
+    let mut text = String::new();
+    for module_text in module.values() {
+        text.push_str(module_text)
     }
+    Some(text)
 }
 
 struct I18nInner {
@@ -320,27 +755,61 @@ struct I18nInner {
     // fixme: this is a relic from the old implementation, and we could gather
     // it only when needed in the future
     resource_text: Vec<String>,
+    // if set, every string returned from tr_() is passed through the given
+    // pseudolocalization transform
+    pseudo: Option<PseudoTranslation>,
+    // bundle errors recorded at construction, and missing-key/format-error/
+    // fallback events recorded by tr_(), drained via I18n::take_diagnostics()
+    diagnostics: Vec<TranslationDiagnostic>,
 }
 
-// Simple number formatting implementation
+/// A single diagnostic event recorded while resolving a translation,
+/// allowing coverage to be audited programmatically instead of by
+/// grepping stdout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationDiagnostic {
+    pub key: String,
+    pub lang: String,
+    pub kind: DiagnosticKind,
+}
 
-fn set_bundle_formatter_for_langs<T>(bundle: &mut FluentBundle<T>, langs: &[LanguageIdentifier]) {
-    let formatter = if want_comma_as_decimal_separator(langs) {
-        format_decimal_with_comma
-    } else {
-        format_decimal_with_period
-    };
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// No bundle had a value for this key; the key name itself was
+    /// returned.
+    MissingKey,
+    /// `format_pattern` reported one or more errors while resolving args.
+    FormatError(String),
+    /// The requested language(s) didn't have the key, and resolution
+    /// fell back to the English template.
+    FellBackToTemplate,
+    /// A translation resource failed to parse, declared a duplicate key,
+    /// or otherwise couldn't be turned into a bundle. A failure in the
+    /// bundled text skips the language entirely; a parse failure in the
+    /// filesystem override text only drops those overrides.
+    BundleError(String),
+}
 
-    bundle.set_formatter(Some(formatter));
+// Number formatting implementation
+//
+// A `NumberFormat` is built per-language and memoized by the bundle's
+// `IntlLangMemoizer`, so repeated `tr_()` calls (e.g. when rendering
+// stats) don't repeatedly recompute locale separators.
+
+fn set_bundle_formatter_for_langs<T>(bundle: &mut FluentBundle<T>, _langs: &[LanguageIdentifier]) {
+    bundle.set_formatter(Some(format_number_value));
 }
 
-fn first_available_num_format_locale(langs: &[LanguageIdentifier]) -> Option<Locale> {
-    for lang in langs {
-        if let Some(locale) = num_format_locale(lang) {
-            return Some(locale);
-        }
+fn format_number_value(
+    val: &FluentValue,
+    intl: &intl_memoizer::concurrent::IntlLangMemoizer,
+) -> Option<String> {
+    match val {
+        FluentValue::Number(num) => intl
+            .with_try_get::<NumberFormat, _, _>((), |nf| nf.format(num))
+            .ok(),
+        _ => None,
     }
-    None
 }
 
 // try to locate a num_format locale for a given language identifier
@@ -356,68 +825,369 @@ fn num_format_locale(lang: &LanguageIdentifier) -> Option<Locale> {
     Locale::from_name(lang.language.as_str()).ok()
 }
 
-fn want_comma_as_decimal_separator(langs: &[LanguageIdentifier]) -> bool {
-    let separator = if let Some(locale) = first_available_num_format_locale(langs) {
-        locale.decimal()
-    } else {
-        "."
+/// A locale-aware number formatter, respecting the options a Fluent
+/// `NUMBER()` placeable may carry (grouping, minimum/maximum fraction
+/// digits, minimum integer digits, and percent style).
+struct NumberFormat {
+    group_separator: &'static str,
+    decimal_separator: &'static str,
+}
+
+impl NumberFormat {
+    fn for_lang(lang: &LanguageIdentifier) -> Self {
+        let locale = num_format_locale(lang).unwrap_or(Locale::en);
+        NumberFormat {
+            group_separator: locale.separator(),
+            decimal_separator: locale.decimal(),
+        }
+    }
+
+    fn format(&self, num: &fluent::types::FluentNumber) -> String {
+        let opts = &num.options;
+
+        let mut value = num.value;
+        let suffix = if opts.style == fluent::types::FluentNumberStyle::Percent {
+            value *= 100.0;
+            "%"
+        } else {
+            ""
+        };
+
+        let max_frac_digits = opts.maximum_fraction_digits.unwrap_or(3);
+        let min_frac_digits = opts.minimum_fraction_digits.unwrap_or(0);
+
+        // render with the maximum allowed precision, then trim back down
+        // to the minimum required precision
+        let rendered = format!("{:.*}", max_frac_digits, value);
+        let trimmed = trim_fraction(&rendered, min_frac_digits);
+
+        let (int_part, frac_part) = match trimmed.split_once('.') {
+            Some((i, f)) => (i, Some(f)),
+            None => (trimmed.as_str(), None),
+        };
+
+        let negative = int_part.starts_with('-');
+        let digits = int_part.trim_start_matches('-');
+
+        let mut int_part = match opts.minimum_integer_digits {
+            Some(min_int) if min_int > digits.len() => format!("{:0>1$}", digits, min_int),
+            _ => digits.to_string(),
+        };
+        if opts.use_grouping {
+            int_part = group_digits(&int_part, self.group_separator);
+        }
+
+        let mut result = String::new();
+        if negative {
+            result.push('-');
+        }
+        result.push_str(&int_part);
+        if let Some(frac) = frac_part {
+            result.push_str(self.decimal_separator);
+            result.push_str(frac);
+        }
+        result.push_str(suffix);
+        result
+    }
+}
+
+impl intl_memoizer::Memoizable for NumberFormat {
+    type Args = ();
+    type Error = std::convert::Infallible;
+
+    fn construct(lang: LanguageIdentifier, _args: Self::Args) -> Result<Self, Self::Error> {
+        Ok(NumberFormat::for_lang(&lang))
+    }
+}
+
+/// Remove excess trailing zeros (and a trailing '.') from a
+/// fixed-precision decimal string, without trimming below `min_frac_digits`.
+fn trim_fraction(rendered: &str, min_frac_digits: usize) -> String {
+    let mut val: Cow<str> = rendered.into();
+    if let Some(pos) = val.find('.') {
+        while val.len() - pos - 1 > min_frac_digits && val.ends_with('0') {
+            val = val[..val.len() - 1].to_string().into();
+        }
+        if val.ends_with('.') {
+            val = val[..val.len() - 1].to_string().into();
+        }
+    }
+    val.to_string()
+}
+
+/// Insert `separator` every three digits from the right, e.g.
+/// `group_digits("1234567", ",") == "1,234,567"`.
+fn group_digits(digits: &str, separator: &str) -> String {
+    let len = digits.len();
+    let mut out = String::with_capacity(len + len / 3 * separator.len());
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (len - i) % 3 == 0 {
+            out.push_str(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
+// DATETIME() builtin
+
+/// Register a `DATETIME($ts, dateStyle: "medium", timeStyle: "short")`
+/// function on the bundle, so .ftl strings can render Unix timestamps
+/// without the caller needing to build date strings in Rust. Mirrors the
+/// `FluentDateTime`/`FluentDateTimeOptions` builtin in Mozilla's
+/// fluent-ffi.
+fn add_datetime_function<T>(bundle: &mut FluentBundle<T>, langs: &[LanguageIdentifier]) {
+    let locale = langs.first().cloned();
+    bundle
+        .add_function("DATETIME", move |positional, named| {
+            format_datetime_value(positional, named, locale.as_ref())
+        })
+        .expect("duplicate DATETIME function");
+}
+
+fn format_datetime_value<'a>(
+    positional: &[FluentValue],
+    named: &FluentArgs,
+    locale: Option<&LanguageIdentifier>,
+) -> FluentValue<'a> {
+    let timestamp = match positional.first() {
+        Some(FluentValue::Number(num)) => num.value as i64,
+        _ => return FluentValue::String("".into()),
     };
 
-    separator == ","
+    let datetime = DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).expect("epoch is always valid"))
+        .naive_utc();
+
+    let date_style = str_option(named, "dateStyle");
+    let time_style = str_option(named, "timeStyle");
+    let weekday = str_option(named, "weekday");
+    let month = str_option(named, "month");
+
+    let calendar = calendar_for_lang(locale);
+
+    let mut parts = vec![];
+    if let Some(weekday) = weekday {
+        parts.push(format_weekday(&datetime, weekday, calendar));
+    }
+    if let Some(style) = date_style {
+        parts.push(format_date_style(&datetime, style, month, locale, calendar));
+    }
+    let mut out = parts.join(", ");
+
+    if let Some(style) = time_style {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(&format_time_style(&datetime, style));
+    }
+
+    FluentValue::String(out.into())
 }
 
-fn format_decimal_with_comma(
-    val: &fluent::FluentValue,
-    _intl: &intl_memoizer::concurrent::IntlLangMemoizer,
-) -> Option<String> {
-    format_number_values(val, Some(","))
+fn str_option<'a>(named: &'a FluentArgs, key: &str) -> Option<&'a str> {
+    match named.get(key) {
+        Some(FluentValue::String(s)) => Some(s.as_ref()),
+        _ => None,
+    }
 }
 
-fn format_decimal_with_period(
-    val: &fluent::FluentValue,
-    _intl: &intl_memoizer::concurrent::IntlLangMemoizer,
-) -> Option<String> {
-    format_number_values(val, None)
+/// Month and weekday names for locale-appropriate `DATETIME()` rendering.
+/// Only a selection of Anki's most widely used bundled languages are
+/// covered here; any other language falls back to English names. This
+/// can grow over time, the same way [LIKELY_SUBTAGS] does.
+struct CalendarNames {
+    months_long: [&'static str; 12],
+    months_short: [&'static str; 12],
+    // indexed from Sunday (0) to Saturday (6)
+    weekdays_long: [&'static str; 7],
+    weekdays_short: [&'static str; 7],
 }
 
-#[inline]
-fn format_number_values(
-    val: &fluent::FluentValue,
-    alt_separator: Option<&'static str>,
-) -> Option<String> {
-    match val {
-        FluentValue::Number(num) => {
-            // create a string with desired maximum digits
-            let max_frac_digits = 2;
-            let with_max_precision = format!(
-                "{number:.precision$}",
-                number = num.value,
-                precision = max_frac_digits
-            );
-
-            // remove any excess trailing zeros
-            let mut val: Cow<str> = with_max_precision.trim_end_matches('0').into();
-
-            // adding back any required to meet minimum_fraction_digits
-            if let Some(minfd) = num.options.minimum_fraction_digits {
-                let pos = val.find('.').expect("expected . in formatted string");
-                let frac_num = val.len() - pos - 1;
-                let zeros_needed = minfd - frac_num;
-                if zeros_needed > 0 {
-                    val = format!("{}{}", val, "0".repeat(zeros_needed)).into();
-                }
-            }
+const EN_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "January", "February", "March", "April", "May", "June", "July", "August", "September",
+        "October", "November", "December",
+    ],
+    months_short: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    weekdays_long: [
+        "Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday",
+    ],
+    weekdays_short: ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+};
 
-            // lop off any trailing '.'
-            let result = val.trim_end_matches('.');
+const FR_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre",
+        "octobre", "novembre", "décembre",
+    ],
+    months_short: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+    weekdays_long: [
+        "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+    ],
+    weekdays_short: ["dim.", "lun.", "mar.", "mer.", "jeu.", "ven.", "sam."],
+};
 
-            if let Some(sep) = alt_separator {
-                Some(result.replace('.', sep))
+const DE_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September",
+        "Oktober", "November", "Dezember",
+    ],
+    months_short: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sep.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+    weekdays_long: [
+        "Sonntag", "Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag",
+    ],
+    weekdays_short: ["So.", "Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa."],
+};
+
+const ES_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre",
+        "octubre", "noviembre", "diciembre",
+    ],
+    months_short: [
+        "ene.", "feb.", "mar.", "abr.", "may.", "jun.", "jul.", "ago.", "sept.", "oct.", "nov.",
+        "dic.",
+    ],
+    weekdays_long: [
+        "domingo", "lunes", "martes", "miércoles", "jueves", "viernes", "sábado",
+    ],
+    weekdays_short: ["dom.", "lun.", "mar.", "mié.", "jue.", "vie.", "sáb."],
+};
+
+const PT_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "janeiro", "fevereiro", "março", "abril", "maio", "junho", "julho", "agosto", "setembro",
+        "outubro", "novembro", "dezembro",
+    ],
+    months_short: [
+        "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.", "nov.",
+        "dez.",
+    ],
+    weekdays_long: [
+        "domingo", "segunda-feira", "terça-feira", "quarta-feira", "quinta-feira",
+        "sexta-feira", "sábado",
+    ],
+    weekdays_short: ["dom.", "seg.", "ter.", "qua.", "qui.", "sex.", "sáb."],
+};
+
+const JA_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    months_short: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    weekdays_long: [
+        "日曜日", "月曜日", "火曜日", "水曜日", "木曜日", "金曜日", "土曜日",
+    ],
+    weekdays_short: ["日", "月", "火", "水", "木", "金", "土"],
+};
+
+const ZH_CALENDAR: CalendarNames = CalendarNames {
+    months_long: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    months_short: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    weekdays_long: [
+        "星期日", "星期一", "星期二", "星期三", "星期四", "星期五", "星期六",
+    ],
+    weekdays_short: ["周日", "周一", "周二", "周三", "周四", "周五", "周六"],
+};
+
+fn calendar_for_lang(locale: Option<&LanguageIdentifier>) -> &'static CalendarNames {
+    match locale.map(|l| l.language.as_str()) {
+        Some("fr") => &FR_CALENDAR,
+        Some("de") => &DE_CALENDAR,
+        Some("es") => &ES_CALENDAR,
+        Some("pt") => &PT_CALENDAR,
+        Some("ja") => &JA_CALENDAR,
+        Some("zh") => &ZH_CALENDAR,
+        _ => &EN_CALENDAR,
+    }
+}
+
+fn format_weekday(dt: &NaiveDateTime, style: &str, calendar: &CalendarNames) -> String {
+    let idx = dt.weekday().num_days_from_sunday() as usize;
+    match style {
+        "short" => calendar.weekdays_short[idx].to_string(),
+        _ => calendar.weekdays_long[idx].to_string(),
+    }
+}
+
+/// English regions that follow day-month-year conventions, mirroring the
+/// en-GB/en-AU special case in [resolve_lang_name]. Bare `en` (which
+/// resolves to the en-US template) and any other unlisted region keep
+/// month-day-year ordering.
+const GB_STYLE_ENGLISH_REGIONS: &[&str] = &["GB", "AU"];
+
+fn format_date_style(
+    dt: &NaiveDateTime,
+    style: &str,
+    month: Option<&str>,
+    locale: Option<&LanguageIdentifier>,
+    calendar: &CalendarNames,
+) -> String {
+    // Only the US English bundle orders day before year with a leading
+    // month; the other bundled English regions (en-GB, en-AU) expect
+    // day-month-year like most other locales. Gate on the GB-style
+    // regions explicitly, rather than "en and not GB", so en-AU (and any
+    // other region added to that list) isn't mistaken for en-US.
+    let us_order = locale.map(|l| l.language.as_str()) == Some("en")
+        && !locale
+            .and_then(|l| l.region)
+            .map(|r| GB_STYLE_ENGLISH_REGIONS.contains(&r.as_str()))
+            .unwrap_or(false);
+
+    let month_idx = dt.month0() as usize;
+    let weekday_long = &calendar.weekdays_long[dt.weekday().num_days_from_sunday() as usize];
+    let day = dt.format("%-d");
+    let year = dt.format("%Y");
+
+    match style {
+        "full" => format!(
+            "{}, {} {}, {}",
+            weekday_long, calendar.months_long[month_idx], day, year
+        ),
+        "long" => format!("{} {}, {}", calendar.months_long[month_idx], day, year),
+        "short" => {
+            if us_order {
+                dt.format("%-m/%-d/%y").to_string()
             } else {
-                Some(result.to_string())
+                dt.format("%-d/%-m/%y").to_string()
             }
         }
-        _ => None,
+        // "medium" and anything else
+        _ => {
+            let month_name = if month == Some("long") {
+                calendar.months_long[month_idx]
+            } else {
+                calendar.months_short[month_idx]
+            };
+            if us_order {
+                format!("{} {}, {}", month_name, day, year)
+            } else {
+                format!("{} {} {}", day, month_name, year)
+            }
+        }
+    }
+}
+
+fn format_time_style(dt: &NaiveDateTime, style: &str) -> String {
+    match style {
+        "full" | "long" => dt.format("%H:%M:%S").to_string(),
+        // "medium" and "short"
+        _ => dt.format("%H:%M").to_string(),
     }
 }
 
@@ -425,6 +1195,27 @@ fn format_number_values(
 pub struct ResourcesForJavascript {
     langs: Vec<String>,
     resources: Vec<String>,
+    direction: CharacterDirection,
+}
+
+/// Whether a locale is written left-to-right or right-to-left.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharacterDirection {
+    Ltr,
+    Rtl,
+}
+
+/// Languages written right-to-left. Matched on the language subtag alone,
+/// so `ar`, `ar-EG` and `he` are all detected even when no script subtag
+/// is present.
+const RTL_LANGUAGES: &[&str] = &["ar", "he", "fa", "ur", "ps", "yi", "dv", "ku", "sd"];
+
+fn text_direction_for_langs(langs: &[LanguageIdentifier]) -> CharacterDirection {
+    match langs.first() {
+        Some(lang) if RTL_LANGUAGES.contains(&lang.language.as_str()) => CharacterDirection::Rtl,
+        _ => CharacterDirection::Ltr,
+    }
 }
 
 #[cfg(test)]
@@ -432,12 +1223,6 @@ mod test {
     use super::*;
     use unic_langid::langid;
 
-    #[test]
-    fn numbers() {
-        assert_eq!(want_comma_as_decimal_separator(&[langid!("en-US")]), false);
-        assert_eq!(want_comma_as_decimal_separator(&[langid!("pl-PL")]), true);
-    }
-
     #[test]
     fn i18n() {
         // English template
@@ -488,4 +1273,161 @@ mod test {
             "two args: 1 and 2.07"
         );
     }
+
+    #[test]
+    fn negotiation_deterministic() {
+        // `available` deliberately lists the two Chinese/Portuguese regional
+        // bundles in the opposite order a hash-map iteration might produce
+        // them in, so a regression back to "whichever bundle is found
+        // first" would be caught regardless of map iteration order.
+        let available = [
+            langid!("zh-TW"),
+            langid!("zh-CN"),
+            langid!("pt-PT"),
+            langid!("pt-BR"),
+        ];
+
+        assert_eq!(
+            negotiate_one(&langid!("zh"), &available),
+            Some(langid!("zh-CN"))
+        );
+        assert_eq!(
+            negotiate_one(&langid!("zh-HK"), &available),
+            Some(langid!("zh-TW"))
+        );
+        assert_eq!(
+            negotiate_one(&langid!("zh-Hant-HK"), &available),
+            Some(langid!("zh-TW"))
+        );
+        assert_eq!(
+            negotiate_one(&langid!("pt"), &available),
+            Some(langid!("pt-BR"))
+        );
+    }
+
+    #[test]
+    fn datetime_formatting() {
+        use chrono::NaiveDate;
+
+        let dt = NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        // en-AU and en-GB use day-month-year order; only en-US (and the
+        // always-appended English template) uses month-day-year
+        assert_eq!(
+            format_date_style(&dt, "short", None, Some(&langid!("en-AU")), &EN_CALENDAR),
+            "5/3/24"
+        );
+        assert_eq!(
+            format_date_style(&dt, "short", None, Some(&langid!("en-GB")), &EN_CALENDAR),
+            "5/3/24"
+        );
+        assert_eq!(
+            format_date_style(&dt, "short", None, Some(&langid!("en-US")), &EN_CALENDAR),
+            "3/5/24"
+        );
+        assert_eq!(
+            format_date_style(&dt, "short", None, Some(&langid!("en")), &EN_CALENDAR),
+            "3/5/24"
+        );
+
+        // month/weekday names follow the locale's own calendar, not
+        // chrono's always-English format specifiers
+        assert_eq!(
+            format_date_style(&dt, "medium", None, Some(&langid!("fr")), &FR_CALENDAR),
+            "5 mars 2024"
+        );
+        assert_eq!(format_weekday(&dt, "long", &FR_CALENDAR), "mardi");
+    }
+
+    #[test]
+    fn negotiate_languages_preference_list() {
+        let available = [langid!("fr"), langid!("de-DE"), langid!("en-GB")];
+
+        // an earlier preference that has no bundle is skipped in favour of
+        // a later one that does
+        assert_eq!(
+            negotiate_languages(&[langid!("it"), langid!("fr")], &available),
+            vec![langid!("fr")]
+        );
+
+        // language+region match, ignoring script
+        assert_eq!(
+            negotiate_languages(&[langid!("de-Latn-DE")], &available),
+            vec![langid!("de-DE")]
+        );
+
+        // macro-language fallback: Cantonese has no bundle, but falls back
+        // to a Chinese one
+        assert_eq!(
+            negotiate_languages(&[langid!("yue")], &[langid!("zh-CN")]),
+            vec![langid!("zh-CN")]
+        );
+
+        // en-GB is matched directly, but bare `en` relies on the
+        // always-appended English template instead, and any preferences
+        // after an English one are never reached
+        assert_eq!(
+            negotiate_languages(&[langid!("en-GB"), langid!("fr")], &available),
+            vec![langid!("en-GB")]
+        );
+        assert_eq!(
+            negotiate_languages(&[langid!("en"), langid!("fr")], &available),
+            vec![]
+        );
+
+        // a language with no bundle and no macro-language fallback
+        // negotiates to nothing
+        assert_eq!(negotiate_languages(&[langid!("xx")], &available), vec![]);
+    }
+
+    #[test]
+    fn number_formatting() {
+        assert_eq!(group_digits("1234567", ","), "1,234,567");
+        assert_eq!(group_digits("123", ","), "123");
+
+        assert_eq!(trim_fraction("1.500", 0), "1.5");
+        assert_eq!(trim_fraction("1.000", 0), "1");
+        assert_eq!(trim_fraction("1.000", 2), "1.00");
+
+        // grouping and decimal separators follow the resolved locale,
+        // not a single hard-coded convention
+        let args = tr_args!["n" => 1234.5];
+        let num = match args.iter().next().unwrap().1 {
+            FluentValue::Number(n) => n,
+            _ => panic!("expected a number"),
+        };
+        assert_eq!(
+            NumberFormat::for_lang(&langid!("en-US")).format(num),
+            "1,234.5"
+        );
+        assert_eq!(
+            NumberFormat::for_lang(&langid!("de-DE")).format(num),
+            "1.234,5"
+        );
+    }
+
+    #[test]
+    fn text_direction() {
+        assert_eq!(
+            text_direction_for_langs(&[langid!("ar-EG")]),
+            CharacterDirection::Rtl
+        );
+        assert_eq!(
+            text_direction_for_langs(&[langid!("he")]),
+            CharacterDirection::Rtl
+        );
+        assert_eq!(
+            text_direction_for_langs(&[langid!("en-US")]),
+            CharacterDirection::Ltr
+        );
+        // only the primary (highest-priority) language matters
+        assert_eq!(
+            text_direction_for_langs(&[langid!("en-US"), langid!("ar")]),
+            CharacterDirection::Ltr
+        );
+        assert_eq!(text_direction_for_langs(&[]), CharacterDirection::Ltr);
+    }
 }